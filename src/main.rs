@@ -1,8 +1,33 @@
-use bevy::{prelude::*, render::texture::ImageSettings, time::FixedTimestep};
+use bevy::{
+    ecs::schedule::ShouldRun,
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+    render::texture::ImageSettings,
+};
 
 const ARENA_WIDTH: i32 = 20;
 const ARENA_HEIGHT: i32 = 20;
 
+const MIN_STEP_INTERVAL: f64 = 0.05;
+const MAX_STEP_INTERVAL: f64 = 2.0;
+const STEP_INTERVAL_INCREMENT: f64 = 0.05;
+
+/// Largest camera lean allowed, in radians.
+const MAX_CAMERA_TILT: f32 = 0.1;
+/// How quickly the camera eases toward its target lean, per second.
+const CAMERA_TILT_RATE: f32 = 4.0;
+
+const MOVE_PREVIEW_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+const MOVE_PREVIEW_BLOCKED_COLOR: Color = Color::rgba(1.0, 0.0, 0.0, 0.3);
+
+/// Depth of markers drawn over the floor tiles, which sit at zero, but under the player.
+const MARKER_Z: f32 = 0.5;
+/// Depth of the player, above the floor and any markers.
+const PLAYER_Z: f32 = 1.0;
+
+const MAX_BREADCRUMBS: usize = 50;
+const BREADCRUMB_ALPHA: f32 = 0.4;
+
 #[derive(Component)]
 enum Direction {
     North,
@@ -11,7 +36,7 @@ enum Direction {
     West,
 }
 
-#[derive(Component, Clone, PartialEq, Eq)]
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
 struct Position {
     x: i32,
     y: i32,
@@ -20,9 +45,46 @@ struct Position {
 #[derive(Component)]
 struct Moving(bool, bool);
 
+/// While set, the direction the entity walks in instead of the one it faces.
+#[derive(Component, Default)]
+struct Strafe(Option<Direction>);
+
+/// A pending lunge in the facing direction, and the steps left before another may start.
+#[derive(Component, Default)]
+struct Dash {
+    tiles_remaining: u32,
+    cooldown: u32,
+}
+
+/// A color multiplied over the entity's sprite, for palette variants of one atlas.
+#[derive(Component)]
+struct Tint(Color);
+
+/// How far above its tile's center an entity's sprite is drawn, in tiles, so tall sprites
+/// can stand on their tile rather than in it.
+#[derive(Component)]
+struct AnchorOffset(f32);
+
+/// A fading marker left on a tile the player walked off of.
+#[derive(Component)]
+struct Breadcrumb {
+    timer: Timer,
+}
+
 #[derive(Component, Debug)]
 struct Tile;
 
+impl Direction {
+    fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
+    }
+}
+
 impl Position {
     fn new(x: i32, y: i32) -> Self {
         Position { x, y }
@@ -35,10 +97,150 @@ struct Player;
 #[derive(Component)]
 struct AdventureTitle;
 
+#[derive(Component)]
+struct StepIntervalText;
+
+/// The translucent marker on the tile the player will step onto next.
+#[derive(Component)]
+struct MovePreview;
+
+/// A key identified by its physical position rather than the character it produces.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct ScanCode(u32);
+
+/// Seconds between fixed steps of `entity_walk`.
+struct StepInterval(f64);
+
+/// How fast game time passes relative to real time; below 1.0 gives players longer to react.
+/// Read from `Settings::game_speed` each time it's needed, so changing the setting applies at
+/// once.
+struct GameSpeed(f32);
+
+impl GameSpeed {
+    /// Falls back to normal speed unless `speed` is finite and positive.
+    fn new(speed: f32) -> Self {
+        if speed.is_finite() && speed > 0.0 {
+            GameSpeed(speed)
+        } else {
+            GameSpeed(1.0)
+        }
+    }
+}
+
+#[derive(Default)]
+struct StepAccumulator {
+    elapsed: f64,
+    pending: u32,
+    looping: bool,
+}
+
+/// How the arena's tiles are laid out, for trying things out before maps exist.
+enum FillPattern {
+    /// Plain floor everywhere.
+    Uniform,
+    /// Two floor tiles alternating like a chessboard.
+    Checkerboard,
+    /// Plain floor with the other floor tile scattered over it, placed by `Settings::fill_seed`.
+    Scatter,
+}
+
+/// A small xorshift generator, so seeded layouts come out the same on every run.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift, so swap it for an arbitrary odd constant.
+        Rng(if seed == 0 {
+            0x9e37_79b9_7f4a_7c15
+        } else {
+            seed
+        })
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// How the player starts and stops walking.
+enum MovementScheme {
+    /// Space toggles walking on and off.
+    Toggle,
+    /// The player walks while a direction key is held.
+    Hold,
+}
+
+struct Settings {
+    /// Whether to show debugging overlays and accept debugging keys.
+    debug: bool,
+    /// Gap between frames in the sprite atlases, in texels.
+    atlas_padding: Vec2,
+    /// How many tiles a dash covers.
+    dash_distance: u32,
+    /// How many steps must pass after a dash before the next one.
+    dash_cooldown: u32,
+    /// Color the player's sprite is tinted with.
+    player_tint: Color,
+    /// Read movement keys by their position on a QWERTY keyboard rather than by the letter
+    /// they produce, so the same keys work on AZERTY, Dvorak and other layouts.
+    use_physical_keys: bool,
+    /// How far the camera leans toward the player's movement, in radians. Zero disables it.
+    camera_tilt: f32,
+    /// The control scheme to start with; M switches between them in game.
+    movement_scheme: MovementScheme,
+    /// The arena layout to start with; F2 cycles through them in debug mode.
+    fill_pattern: FillPattern,
+    /// Seed for the scatter pattern, so a layout can be reproduced.
+    fill_seed: u64,
+    /// How far above its tile the player's sprite is drawn, in tiles.
+    player_anchor_offset: f32,
+    /// Whether the player leaves breadcrumbs behind; B toggles this in game.
+    breadcrumbs: bool,
+    /// Seconds a breadcrumb takes to fade away.
+    breadcrumb_ttl: f32,
+    /// Whether to mark the tile the player will step onto next; G toggles this in game.
+    move_preview: bool,
+    /// The most steps one frame may catch up on. Time owed past them is dropped, so a long
+    /// stall doesn't come back as a burst of catch-up steps.
+    max_steps_per_frame: u32,
+    /// How fast game time passes relative to real time, for players who need longer to react.
+    game_speed: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            debug: cfg!(debug_assertions),
+            atlas_padding: Vec2::ZERO,
+            dash_distance: 3,
+            dash_cooldown: 5,
+            player_tint: Color::WHITE,
+            use_physical_keys: false,
+            camera_tilt: 0.0,
+            movement_scheme: MovementScheme::Toggle,
+            fill_pattern: FillPattern::Uniform,
+            fill_seed: 0,
+            player_anchor_offset: 0.0,
+            breadcrumbs: false,
+            breadcrumb_ttl: 5.0,
+            move_preview: false,
+            max_steps_per_frame: 3,
+            game_speed: 1.0,
+        }
+    }
+}
+
 fn main() {
     App::new()
         .insert_resource(ImageSettings::default_nearest())
         .insert_resource(ClearColor(Color::rgb(0.04, 0.04, 0.04)))
+        .insert_resource(Settings::default())
+        .insert_resource(StepInterval(0.3))
+        .insert_resource(StepAccumulator::default())
+        .insert_resource(Input::<ScanCode>::default())
         .insert_resource(WindowDescriptor {
             title: "Adventure".to_string(),
             width: 1500.,
@@ -47,72 +249,477 @@ fn main() {
         })
         .add_plugins(DefaultPlugins)
         .add_startup_system(setup)
+        .add_system_to_stage(CoreStage::PreUpdate, scan_code_input)
         .add_system(animate_player_sprite)
         .add_system(animate_tiles)
+        .add_system(tint_sprites)
+        .add_system(camera_tilt)
         .add_system(change_player_direction)
+        .add_system(switch_movement_scheme)
         .add_system(move_player)
+        .add_system(dash_player)
+        .add_system(adjust_step_interval)
+        .add_system(show_step_interval)
+        .add_system(cycle_fill_pattern)
+        .add_system(toggle_breadcrumbs)
+        .add_system(drop_breadcrumbs)
+        .add_system(fade_breadcrumbs)
+        .add_system(toggle_move_preview)
+        .add_system(move_preview)
         .add_system_set(
             SystemSet::new()
-                .with_run_criteria(FixedTimestep::step(0.3))
+                .with_run_criteria(step_timer)
                 .with_system(entity_walk),
         )
         .run();
 }
 
+fn step_timer(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    interval: Res<StepInterval>,
+    mut accumulator: ResMut<StepAccumulator>,
+) -> ShouldRun {
+    if !accumulator.looping {
+        let speed = GameSpeed::new(settings.game_speed);
+        let (steps, elapsed) = steps_due(
+            accumulator.elapsed,
+            time.delta_seconds_f64() * speed.0 as f64,
+            interval.0,
+            settings.max_steps_per_frame,
+        );
+        accumulator.pending = steps;
+        accumulator.elapsed = elapsed;
+    }
+    if accumulator.pending > 0 {
+        accumulator.pending -= 1;
+        accumulator.looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        accumulator.looping = false;
+        ShouldRun::No
+    }
+}
+
+/// How many steps of `interval` seconds are due once `delta` more seconds have passed on top
+/// of `elapsed`, capped at `max_steps`, and the time left toward the next one.
+fn steps_due(elapsed: f64, delta: f64, interval: f64, max_steps: u32) -> (u32, f64) {
+    let elapsed = elapsed + delta;
+    let steps = (elapsed / interval).floor();
+    (
+        steps.min(max_steps as f64) as u32,
+        elapsed - steps * interval,
+    )
+}
+
+fn adjust_step_interval(
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut interval: ResMut<StepInterval>,
+) {
+    if !settings.debug {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Equals) {
+        interval.0 = (interval.0 + STEP_INTERVAL_INCREMENT).min(MAX_STEP_INTERVAL);
+    }
+    if keyboard_input.just_pressed(KeyCode::Minus) {
+        interval.0 = (interval.0 - STEP_INTERVAL_INCREMENT).max(MIN_STEP_INTERVAL);
+    }
+}
+
+fn show_step_interval(
+    interval: Res<StepInterval>,
+    mut query: Query<&mut Text, With<StepIntervalText>>,
+) {
+    if let Some(mut text) = query.iter_mut().next() {
+        text.sections[0].value = format!("step {:.2}s ({:.1}/s)", interval.0, 1.0 / interval.0);
+    }
+}
+
+/// Keeps `Input<ScanCode>` up to date the way Bevy keeps `Input<KeyCode>`.
+fn scan_code_input(
+    mut scan_code_input: ResMut<Input<ScanCode>>,
+    mut keyboard_input_events: EventReader<KeyboardInput>,
+) {
+    scan_code_input.clear();
+    for event in keyboard_input_events.iter() {
+        match event.state {
+            ButtonState::Pressed => scan_code_input.press(ScanCode(event.scan_code)),
+            ButtonState::Released => scan_code_input.release(ScanCode(event.scan_code)),
+        }
+    }
+}
+
+/// The scan code of the key in `key`'s position on a QWERTY keyboard, for the keys we
+/// support reading physically.
+fn scan_code_for(key: KeyCode) -> Option<ScanCode> {
+    #[cfg(target_os = "macos")]
+    let scan_code = match key {
+        KeyCode::W => 0x0d,
+        KeyCode::A => 0x00,
+        KeyCode::S => 0x01,
+        KeyCode::D => 0x02,
+        KeyCode::Space => 0x31,
+        KeyCode::Q => 0x0c,
+        _ => return None,
+    };
+    #[cfg(not(target_os = "macos"))]
+    let scan_code = match key {
+        KeyCode::W => 0x11,
+        KeyCode::A => 0x1e,
+        KeyCode::S => 0x1f,
+        KeyCode::D => 0x20,
+        KeyCode::Space => 0x39,
+        KeyCode::Q => 0x10,
+        _ => return None,
+    };
+    Some(ScanCode(scan_code))
+}
+
+fn key_pressed(
+    keyboard_input: &Input<KeyCode>,
+    scan_code_input: &Input<ScanCode>,
+    settings: &Settings,
+    key: KeyCode,
+) -> bool {
+    match scan_code_for(key) {
+        Some(scan_code) if settings.use_physical_keys => scan_code_input.pressed(scan_code),
+        _ => keyboard_input.pressed(key),
+    }
+}
+
+fn key_just_pressed(
+    keyboard_input: &Input<KeyCode>,
+    scan_code_input: &Input<ScanCode>,
+    settings: &Settings,
+    key: KeyCode,
+) -> bool {
+    match scan_code_for(key) {
+        Some(scan_code) if settings.use_physical_keys => scan_code_input.just_pressed(scan_code),
+        _ => keyboard_input.just_pressed(key),
+    }
+}
+
+fn key_just_released(
+    keyboard_input: &Input<KeyCode>,
+    scan_code_input: &Input<ScanCode>,
+    settings: &Settings,
+    key: KeyCode,
+) -> bool {
+    match scan_code_for(key) {
+        Some(scan_code) if settings.use_physical_keys => scan_code_input.just_released(scan_code),
+        _ => keyboard_input.just_released(key),
+    }
+}
+
+fn cycle_fill_pattern(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut query: Query<(&Position, &mut TextureAtlasSprite), With<Tile>>,
+) {
+    if settings.debug && keyboard_input.just_pressed(KeyCode::F2) {
+        settings.fill_pattern = match settings.fill_pattern {
+            FillPattern::Uniform => FillPattern::Checkerboard,
+            FillPattern::Checkerboard => FillPattern::Scatter,
+            FillPattern::Scatter => FillPattern::Uniform,
+        };
+        let tiles = fill_arena(&settings.fill_pattern, &mut Rng::new(settings.fill_seed));
+        for (position, mut sprite) in query.iter_mut() {
+            sprite.index = tiles[(position.y * ARENA_WIDTH + position.x) as usize].1;
+        }
+    }
+}
+
+/// The direction of the last of the WASD keys `is_down` holds for, if any.
+fn direction_from_keys(is_down: impl Fn(KeyCode) -> bool) -> Option<Direction> {
+    [
+        (KeyCode::W, Direction::North),
+        (KeyCode::A, Direction::West),
+        (KeyCode::S, Direction::South),
+        (KeyCode::D, Direction::East),
+    ]
+    .into_iter()
+    .filter(|(key, _)| is_down(*key))
+    .map(|(_, direction)| direction)
+    .last()
+}
+
 fn change_player_direction(
     keyboard_input: Res<Input<KeyCode>>,
-    mut query: Query<&mut Direction, With<Player>>,
+    scan_code_input: Res<Input<ScanCode>>,
+    settings: Res<Settings>,
+    mut query: Query<(&mut Direction, &mut Strafe), With<Player>>,
 ) {
-    if let Some(mut direction) = query.iter_mut().next() {
-        if keyboard_input.pressed(KeyCode::W) {
-            *direction = Direction::North;
+    if let Some((mut direction, mut strafe)) = query.iter_mut().next() {
+        let strafing = keyboard_input.pressed(KeyCode::LAlt);
+        if strafing {
+            let pressed_direction = direction_from_keys(|key| {
+                key_pressed(&keyboard_input, &scan_code_input, &settings, key)
+            });
+            if pressed_direction.is_some() {
+                strafe.0 = pressed_direction;
+            }
+        } else {
+            if strafe.0.is_some() {
+                strafe.0 = None;
+            }
+            // Only re-read the held keys when one of them changes, so holding a key doesn't undo
+            // a quick turn.
+            let direction_keys_changed = [KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D]
+                .into_iter()
+                .any(|key| {
+                    key_just_pressed(&keyboard_input, &scan_code_input, &settings, key)
+                        || key_just_released(&keyboard_input, &scan_code_input, &settings, key)
+                });
+            if direction_keys_changed {
+                if let Some(pressed_direction) = direction_from_keys(|key| {
+                    key_pressed(&keyboard_input, &scan_code_input, &settings, key)
+                }) {
+                    *direction = pressed_direction;
+                }
+            }
         }
-        if keyboard_input.pressed(KeyCode::A) {
-            *direction = Direction::West;
+        if key_just_pressed(&keyboard_input, &scan_code_input, &settings, KeyCode::Q) {
+            *direction = direction.opposite();
         }
-        if keyboard_input.pressed(KeyCode::S) {
-            *direction = Direction::South;
+    }
+}
+
+fn switch_movement_scheme(keyboard_input: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keyboard_input.just_pressed(KeyCode::M) {
+        settings.movement_scheme = match settings.movement_scheme {
+            MovementScheme::Toggle => MovementScheme::Hold,
+            MovementScheme::Hold => MovementScheme::Toggle,
+        };
+    }
+}
+
+fn move_player(
+    keyboard_input: Res<Input<KeyCode>>,
+    scan_code_input: Res<Input<ScanCode>>,
+    settings: Res<Settings>,
+    mut query: Query<&mut Moving, With<Player>>,
+) {
+    if let Some(mut moving) = query.iter_mut().next() {
+        match settings.movement_scheme {
+            MovementScheme::Toggle => {
+                if key_just_released(&keyboard_input, &scan_code_input, &settings, KeyCode::Space) {
+                    moving.0 = !moving.0;
+                }
+            }
+            MovementScheme::Hold => {
+                let held = [KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D]
+                    .into_iter()
+                    .any(|key| key_pressed(&keyboard_input, &scan_code_input, &settings, key));
+                if moving.0 != held {
+                    moving.0 = held;
+                }
+            }
+        }
+    }
+}
+
+fn dash_player(
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut query: Query<&mut Dash, With<Player>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::LShift) {
+        if let Some(mut dash) = query.iter_mut().next() {
+            if dash.cooldown == 0 && dash.tiles_remaining == 0 {
+                dash.tiles_remaining = settings.dash_distance;
+            }
         }
-        if keyboard_input.pressed(KeyCode::D) {
-            *direction = Direction::East;
+    }
+}
+
+fn step_toward(position: &Position, direction: &Direction) -> Position {
+    match direction {
+        Direction::North => {
+            Position::new(position.x, std::cmp::min(position.y + 1, ARENA_HEIGHT - 1))
+        }
+        Direction::South => Position::new(position.x, std::cmp::max(position.y - 1, 0)),
+        Direction::East => {
+            Position::new(std::cmp::min(position.x + 1, ARENA_WIDTH - 1), position.y)
         }
+        Direction::West => Position::new(std::cmp::max(position.x - 1, 0), position.y),
     }
 }
 
-fn move_player(keyboard_input: Res<Input<KeyCode>>, mut query: Query<&mut Moving, With<Player>>) {
-    if keyboard_input.just_released(KeyCode::Space) {
-        if let Some(mut moving) = query.iter_mut().next() {
-            moving.0 = !moving.0;
+/// The direction an entity facing `direction` walks in, which differs while it strafes.
+fn walk_direction<'a>(direction: &'a Direction, strafe: Option<&'a Strafe>) -> &'a Direction {
+    strafe
+        .and_then(|strafe| strafe.0.as_ref())
+        .unwrap_or(direction)
+}
+
+/// Where a dash of up to `tiles` steps from `position` ends, stopping early at a wall.
+fn dash_destination(position: &Position, direction: &Direction, tiles: u32) -> Position {
+    let mut position = position.clone();
+    for _ in 0..tiles {
+        let next_position = step_toward(&position, direction);
+        if next_position == position {
+            break;
         }
+        position = next_position;
     }
+    position
 }
 
-fn entity_walk(mut query: Query<(&Direction, &mut Moving, &mut Position)>) {
-    for (direction, mut moving, mut position) in query.iter_mut() {
+/// The components `entity_walk` steps an entity with.
+type Walker = (
+    &'static Direction,
+    &'static mut Moving,
+    &'static mut Position,
+    Option<&'static mut Dash>,
+    Option<&'static Strafe>,
+);
+
+fn entity_walk(settings: Res<Settings>, mut query: Query<Walker>) {
+    for (direction, mut moving, mut position, dash, strafe) in query.iter_mut() {
+        if let Some(mut dash) = dash {
+            if dash.tiles_remaining > 0 {
+                *position = dash_destination(&position, direction, dash.tiles_remaining);
+                dash.tiles_remaining = 0;
+                dash.cooldown = settings.dash_cooldown;
+                continue;
+            }
+            dash.cooldown = dash.cooldown.saturating_sub(1);
+        }
         if moving.0 {
             moving.1 = !moving.1;
-            let previous_position = position.clone();
-            match direction {
-                Direction::North => {
-                    position.y = std::cmp::min(position.y + 1, ARENA_HEIGHT - 1);
-                }
-                Direction::South => {
-                    position.y = std::cmp::max(position.y - 1, 0);
-                }
-                Direction::East => {
-                    position.x = std::cmp::min(position.x + 1, ARENA_WIDTH - 1);
-                }
-                Direction::West => {
-                    position.x = std::cmp::max(position.x - 1, 0);
-                }
-            }
-            if *position == previous_position {
+            let next_position = step_toward(&position, walk_direction(direction, strafe));
+            if next_position == *position {
                 moving.0 = false;
             }
+            *position = next_position;
         }
     }
 }
 
+fn toggle_breadcrumbs(keyboard_input: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keyboard_input.just_pressed(KeyCode::B) {
+        settings.breadcrumbs = !settings.breadcrumbs;
+    }
+}
+
+fn drop_breadcrumbs(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    windows: Res<Windows>,
+    mut last_position: Local<Option<Position>>,
+    player_query: Query<&Position, With<Player>>,
+    breadcrumb_query: Query<(Entity, &Breadcrumb)>,
+) {
+    if let Some(position) = player_query.iter().next() {
+        if let Some(previous_position) = last_position.replace(position.clone()) {
+            if settings.breadcrumbs && previous_position != *position {
+                if breadcrumb_query.iter().count() >= MAX_BREADCRUMBS {
+                    if let Some((oldest, _)) = breadcrumb_query
+                        .iter()
+                        .max_by_key(|(_, breadcrumb)| breadcrumb.timer.elapsed())
+                    {
+                        commands.entity(oldest).despawn();
+                    }
+                }
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgba(1.0, 1.0, 1.0, BREADCRUMB_ALPHA),
+                            custom_size: Some(Vec2::splat(4.0)),
+                            ..default()
+                        },
+                        transform: Transform {
+                            translation: marker_translation(&previous_position, &windows),
+                            scale: Vec3::splat(6.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(previous_position)
+                    .insert(Breadcrumb {
+                        timer: Timer::from_seconds(settings.breadcrumb_ttl, false),
+                    });
+            }
+        }
+    }
+}
+
+fn fade_breadcrumbs(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    windows: Res<Windows>,
+    mut query: Query<(
+        Entity,
+        &Position,
+        &mut Breadcrumb,
+        &mut Sprite,
+        &mut Transform,
+    )>,
+) {
+    let speed = GameSpeed::new(settings.game_speed);
+    for (entity, position, mut breadcrumb, mut sprite, mut transform) in query.iter_mut() {
+        breadcrumb.timer.tick(time.delta().mul_f32(speed.0));
+        if breadcrumb.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        sprite
+            .color
+            .set_a(BREADCRUMB_ALPHA * breadcrumb.timer.percent_left());
+        transform.translation = marker_translation(position, &windows);
+    }
+}
+
+/// Where a marker on `position` is drawn: over the floor, but under the player.
+fn marker_translation(position: &Position, windows: &Windows) -> Vec3 {
+    let (x, y) = match windows.get_primary() {
+        Some(window) => (
+            convert(position.x as f32, window.width(), ARENA_WIDTH as f32),
+            convert(position.y as f32, window.height(), ARENA_HEIGHT as f32),
+        ),
+        None => (0.0, 0.0),
+    };
+    Vec3::new(x, y, MARKER_Z)
+}
+
+fn toggle_move_preview(keyboard_input: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keyboard_input.just_pressed(KeyCode::G) {
+        settings.move_preview = !settings.move_preview;
+    }
+}
+
+fn move_preview(
+    settings: Res<Settings>,
+    windows: Res<Windows>,
+    player_query: Query<(&Direction, &Position, Option<&Strafe>), With<Player>>,
+    mut preview_query: Query<(&mut Sprite, &mut Transform, &mut Visibility), With<MovePreview>>,
+) {
+    if let Some((mut sprite, mut transform, mut visibility)) = preview_query.iter_mut().next() {
+        visibility.is_visible = settings.move_preview;
+        if !settings.move_preview {
+            return;
+        }
+        if let Some((direction, position, strafe)) = player_query.iter().next() {
+            let target = step_toward(position, walk_direction(direction, strafe));
+            sprite.color = move_preview_color(position, &target);
+            // A blocked step targets the player's own tile, so this must stay under the player.
+            transform.translation = marker_translation(&target, &windows);
+        }
+    }
+}
+
+/// The preview's color for a step from `position` to `target`, red when a wall blocks it.
+fn move_preview_color(position: &Position, target: &Position) -> Color {
+    if target == position {
+        MOVE_PREVIEW_BLOCKED_COLOR
+    } else {
+        MOVE_PREVIEW_COLOR
+    }
+}
+
 const PLAYER_SPRITE_NORTH: usize = 40;
 const PLAYER_SPRITE_SOUTH: usize = 4;
 const PLAYER_SPRITE_EAST: usize = 28;
@@ -140,47 +747,85 @@ fn body_sprite_for(direction: &Direction, moving: &Moving) -> usize {
     }
 }
 
-fn animate_player_sprite(
-    windows: Res<Windows>,
-    mut query: Query<
-        (
-            &Direction,
-            &Moving,
-            &Position,
-            &mut TextureAtlasSprite,
-            &mut Transform,
-        ),
-        With<Player>,
-    >,
-) {
-    if let Some((direction, moving, position, mut sprite, mut transform)) = query.iter_mut().next()
+/// The components `animate_player_sprite` draws the player from.
+type PlayerSprite = (
+    &'static Direction,
+    &'static Moving,
+    &'static Position,
+    Option<&'static AnchorOffset>,
+    &'static mut TextureAtlasSprite,
+    &'static mut Transform,
+);
+
+fn animate_player_sprite(windows: Res<Windows>, mut query: Query<PlayerSprite, With<Player>>) {
+    if let Some((direction, moving, position, anchor_offset, mut sprite, mut transform)) =
+        query.iter_mut().next()
     {
         if let Some(window) = windows.get_primary() {
             sprite.index = body_sprite_for(direction, moving);
-            transform.translation = Vec3::new(
-                convert(position.x as f32, window.width() as f32, ARENA_WIDTH as f32),
-                convert(
-                    position.y as f32,
-                    window.height() as f32,
-                    ARENA_HEIGHT as f32,
-                ),
-                0.0,
-            );
+            transform.translation = Vec3 {
+                z: PLAYER_Z,
+                ..sprite_translation(
+                    position,
+                    anchor_offset,
+                    Vec2::new(window.width(), window.height()),
+                )
+            };
         }
     }
 }
 
+/// Where the sprite of an entity on `position` is drawn in a window of `window_size`, lifted
+/// by its anchor offset if it has one.
+fn sprite_translation(
+    position: &Position,
+    anchor_offset: Option<&AnchorOffset>,
+    window_size: Vec2,
+) -> Vec3 {
+    let anchor_offset = anchor_offset.map_or(0.0, |anchor_offset| anchor_offset.0);
+    Vec3::new(
+        convert(position.x as f32, window_size.x, ARENA_WIDTH as f32),
+        convert(
+            position.y as f32 + anchor_offset,
+            window_size.y,
+            ARENA_HEIGHT as f32,
+        ),
+        0.0,
+    )
+}
+
 fn convert(pos: f32, bound_window: f32, bound_game: f32) -> f32 {
     let tile_size = bound_window / bound_game;
     pos / bound_game * bound_window - (bound_window / 2.) + (tile_size / 2.)
 }
 
-fn animate_tiles(
-    windows: Res<Windows>,
-    mut query: Query<(&Position, &mut TextureAtlasSprite, &mut Transform), With<Tile>>,
-) {
-    for (position, mut sprite, mut transform) in query.iter_mut() {
-        sprite.index = 5;
+const TILE_SPRITE_FLOOR: usize = 5;
+const TILE_SPRITE_FLOOR_ALTERNATE: usize = 7;
+/// One in this many tiles gets the alternate floor in the scatter pattern.
+const SCATTER_ODDS: u64 = 4;
+
+/// Every tile of the arena in row order, with the sprite `pattern` gives it.
+fn fill_arena(pattern: &FillPattern, rng: &mut Rng) -> Vec<(Position, usize)> {
+    let mut tiles = Vec::new();
+    for y in 0..ARENA_HEIGHT {
+        for x in 0..ARENA_WIDTH {
+            let sprite = match pattern {
+                FillPattern::Uniform => TILE_SPRITE_FLOOR,
+                FillPattern::Checkerboard if (x + y) % 2 == 0 => TILE_SPRITE_FLOOR,
+                FillPattern::Checkerboard => TILE_SPRITE_FLOOR_ALTERNATE,
+                FillPattern::Scatter if rng.next_u64().is_multiple_of(SCATTER_ODDS) => {
+                    TILE_SPRITE_FLOOR_ALTERNATE
+                }
+                FillPattern::Scatter => TILE_SPRITE_FLOOR,
+            };
+            tiles.push((Position::new(x, y), sprite));
+        }
+    }
+    tiles
+}
+
+fn animate_tiles(windows: Res<Windows>, mut query: Query<(&Position, &mut Transform), With<Tile>>) {
+    for (position, mut transform) in query.iter_mut() {
         if let Some(window) = windows.get_primary() {
             transform.translation = Vec3::new(
                 convert(position.x as f32, window.width() as f32, ARENA_WIDTH as f32),
@@ -195,14 +840,49 @@ fn animate_tiles(
     }
 }
 
+fn camera_tilt(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    player_query: Query<(&Direction, &Moving, Option<&Strafe>), With<Player>>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    let tilt = settings.camera_tilt.clamp(0.0, MAX_CAMERA_TILT);
+    let target_angle = match player_query.iter().next() {
+        Some((direction, Moving(true, _), strafe)) => match walk_direction(direction, strafe) {
+            Direction::East => -tilt,
+            Direction::West => tilt,
+            _ => 0.0,
+        },
+        _ => 0.0,
+    };
+    if let Some(mut transform) = camera_query.iter_mut().next() {
+        let target = Quat::from_rotation_z(target_angle);
+        let ease = (time.delta_seconds() * CAMERA_TILT_RATE).min(1.0);
+        transform.rotation = transform.rotation.slerp(target, ease);
+    }
+}
+
+fn tint_sprites(mut query: Query<(&Tint, &mut TextureAtlasSprite), Changed<Tint>>) {
+    for (tint, mut sprite) in query.iter_mut() {
+        sprite.color = tint.0;
+    }
+}
+
 fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 ) {
     let characters_texture_handle = asset_server.load("characters.png");
-    let characters_texture_atlas =
-        TextureAtlas::from_grid(characters_texture_handle, Vec2::new(16.0, 16.0), 12, 8);
+    let characters_texture_atlas = TextureAtlas::from_grid_with_padding(
+        characters_texture_handle,
+        Vec2::new(16.0, 16.0),
+        12,
+        8,
+        settings.atlas_padding,
+        Vec2::ZERO,
+    );
     let characters_texture_atlas_handle = texture_atlases.add(characters_texture_atlas);
 
     commands.spawn_bundle(Camera2dBundle::default());
@@ -215,22 +895,44 @@ fn setup(
         .insert(Direction::North)
         .insert(Position::new(0, 0))
         .insert(Moving(false, true))
+        .insert(Strafe::default())
+        .insert(Dash::default())
+        .insert(Tint(settings.player_tint))
+        .insert(AnchorOffset(settings.player_anchor_offset))
         .insert(Player);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: MOVE_PREVIEW_COLOR,
+                custom_size: Some(Vec2::splat(16.0)),
+                ..default()
+            },
+            transform: Transform::from_scale(Vec3::splat(6.0)),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(MovePreview);
     let basictiles_texture_handle = asset_server.load("basictiles.png");
-    let basictiles_texture_atlas =
-        TextureAtlas::from_grid(basictiles_texture_handle, Vec2::new(16.0, 16.0), 8, 4);
+    let basictiles_texture_atlas = TextureAtlas::from_grid_with_padding(
+        basictiles_texture_handle,
+        Vec2::new(16.0, 16.0),
+        8,
+        4,
+        settings.atlas_padding,
+        Vec2::ZERO,
+    );
     let basictiles_texture_atlas_handle = texture_atlases.add(basictiles_texture_atlas);
-    for y in 0..ARENA_HEIGHT {
-        for x in 0..ARENA_WIDTH {
-            commands
-                .spawn_bundle(SpriteSheetBundle {
-                    texture_atlas: basictiles_texture_atlas_handle.clone(),
-                    transform: Transform::from_scale(Vec3::splat(6.0)),
-                    ..default()
-                })
-                .insert(Position { x, y })
-                .insert(Tile);
-        }
+    for (position, sprite) in fill_arena(&settings.fill_pattern, &mut Rng::new(settings.fill_seed))
+    {
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                sprite: TextureAtlasSprite::new(sprite),
+                texture_atlas: basictiles_texture_atlas_handle.clone(),
+                transform: Transform::from_scale(Vec3::splat(6.0)),
+                ..default()
+            })
+            .insert(position)
+            .insert(Tile);
     }
     commands
         .spawn_bundle(
@@ -255,4 +957,440 @@ fn setup(
             }),
         )
         .insert(AdventureTitle);
+    if settings.debug {
+        commands
+            .spawn_bundle(
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(10.0),
+                        left: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                }),
+            )
+            .insert(StepIntervalText);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dash_covers_its_distance() {
+        let destination = dash_destination(&Position::new(0, 0), &Direction::East, 3);
+        assert_eq!(destination, Position::new(3, 0));
+    }
+
+    #[test]
+    fn dash_stops_at_a_wall() {
+        let destination = dash_destination(&Position::new(18, 0), &Direction::East, 3);
+        assert_eq!(destination, Position::new(19, 0));
+    }
+
+    #[test]
+    fn tint_is_set_on_the_sprite() {
+        let mut world = World::new();
+        let entity = world
+            .spawn()
+            .insert(Tint(Color::RED))
+            .insert(TextureAtlasSprite::default())
+            .id();
+        SystemStage::single(tint_sprites).run(&mut world);
+        assert_eq!(
+            world.get::<TextureAtlasSprite>(entity).unwrap().color,
+            Color::RED
+        );
+    }
+
+    #[test]
+    fn releasing_keys_in_hold_mode_stops_the_next_step() {
+        let mut world = World::new();
+        world.insert_resource(Settings {
+            movement_scheme: MovementScheme::Hold,
+            ..default()
+        });
+        world.insert_resource(Input::<KeyCode>::default());
+        world.insert_resource(Input::<ScanCode>::default());
+        let player = world
+            .spawn()
+            .insert(Direction::North)
+            .insert(Position::new(0, 0))
+            .insert(Moving(false, true))
+            .insert(Player)
+            .id();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(move_player)
+            .with_system(entity_walk.after(move_player));
+
+        world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+        stage.run(&mut world);
+        assert_eq!(world.get::<Position>(player), Some(&Position::new(0, 1)));
+
+        world.resource_mut::<Input<KeyCode>>().release(KeyCode::W);
+        stage.run(&mut world);
+        assert!(!world.get::<Moving>(player).unwrap().0);
+        assert_eq!(world.get::<Position>(player), Some(&Position::new(0, 1)));
+    }
+
+    #[test]
+    fn checkerboard_alternates_across_the_grid() {
+        let tiles = fill_arena(&FillPattern::Checkerboard, &mut Rng::new(0));
+        let sprite_at = |x, y| {
+            let (position, sprite) = &tiles[(y * ARENA_WIDTH + x) as usize];
+            assert_eq!(position, &Position::new(x, y));
+            *sprite
+        };
+        assert_eq!(sprite_at(0, 0), TILE_SPRITE_FLOOR);
+        assert_eq!(sprite_at(1, 0), TILE_SPRITE_FLOOR_ALTERNATE);
+        assert_eq!(sprite_at(0, 1), TILE_SPRITE_FLOOR_ALTERNATE);
+        assert_eq!(sprite_at(1, 1), TILE_SPRITE_FLOOR);
+    }
+
+    #[test]
+    fn scatter_is_reproducible_from_its_seed() {
+        let scatter = |seed| fill_arena(&FillPattern::Scatter, &mut Rng::new(seed));
+        assert_eq!(scatter(7), scatter(7));
+        assert_ne!(scatter(7), scatter(8));
+        assert!(scatter(7)
+            .iter()
+            .any(|(_, sprite)| *sprite == TILE_SPRITE_FLOOR_ALTERNATE));
+    }
+
+    #[test]
+    fn strafing_moves_without_turning() {
+        let mut world = World::new();
+        world.insert_resource(Settings::default());
+        world.insert_resource(Input::<KeyCode>::default());
+        world.insert_resource(Input::<ScanCode>::default());
+        let player = world
+            .spawn()
+            .insert(Direction::North)
+            .insert(Strafe::default())
+            .insert(Position::new(0, 0))
+            .insert(Moving(true, true))
+            .insert(Player)
+            .id();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(change_player_direction)
+            .with_system(entity_walk.after(change_player_direction));
+
+        world.resource_mut::<Input<KeyCode>>().press(KeyCode::LAlt);
+        world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+        stage.run(&mut world);
+        assert_eq!(world.get::<Position>(player), Some(&Position::new(1, 0)));
+        assert!(matches!(
+            world.get::<Direction>(player),
+            Some(Direction::North)
+        ));
+    }
+
+    #[test]
+    fn anchor_offset_shifts_the_translation() {
+        let window_size = Vec2::new(1500.0, 1500.0);
+        let tile_size = window_size.y / ARENA_HEIGHT as f32;
+        let position = Position::new(3, 3);
+        let centered = sprite_translation(&position, None, window_size);
+        let anchored = sprite_translation(&position, Some(&AnchorOffset(0.5)), window_size);
+        assert_eq!(anchored.x, centered.x);
+        assert!((anchored.y - centered.y - 0.5 * tile_size).abs() < 1e-3);
+    }
+
+    #[test]
+    fn moving_leaves_a_breadcrumb_that_fades_away() {
+        let mut world = World::new();
+        world.insert_resource(Settings {
+            breadcrumbs: true,
+            breadcrumb_ttl: 1.0,
+            ..default()
+        });
+        world.insert_resource(Windows::default());
+        let mut time = Time::default();
+        time.update();
+        world.insert_resource(time);
+        let player = world
+            .spawn()
+            .insert(Position::new(0, 0))
+            .insert(Player)
+            .id();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(drop_breadcrumbs)
+            .with_system(fade_breadcrumbs);
+        let mut breadcrumbs = world.query_filtered::<&Position, With<Breadcrumb>>();
+
+        stage.run(&mut world);
+        *world.get_mut::<Position>(player).unwrap() = Position::new(1, 0);
+        stage.run(&mut world);
+        assert_eq!(
+            breadcrumbs.iter(&world).collect::<Vec<_>>(),
+            [&Position::new(0, 0)]
+        );
+        // Breadcrumbs sit between the floor and the player, who walks back over them.
+        assert!(world
+            .query_filtered::<&Transform, With<Breadcrumb>>()
+            .iter(&world)
+            .all(|transform| transform.translation.z > 0.0 && transform.translation.z < PLAYER_Z));
+
+        let mut time = world.resource_mut::<Time>();
+        let last_update = time.last_update().unwrap();
+        time.update_with_instant(last_update + std::time::Duration::from_secs_f32(1.5));
+        stage.run(&mut world);
+        assert_eq!(breadcrumbs.iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn move_preview_targets_the_adjacent_tile() {
+        let position = Position::new(3, 3);
+        let target = step_toward(&position, &Direction::North);
+        assert_eq!(target, Position::new(3, 4));
+        assert_eq!(move_preview_color(&position, &target), MOVE_PREVIEW_COLOR);
+    }
+
+    #[test]
+    fn move_preview_flags_a_blocked_target() {
+        let position = Position::new(0, 3);
+        let target = step_toward(&position, &Direction::West);
+        assert_eq!(target, position);
+        assert_eq!(
+            move_preview_color(&position, &target),
+            MOVE_PREVIEW_BLOCKED_COLOR
+        );
+    }
+
+    #[test]
+    fn blocked_move_preview_is_drawn_under_the_player() {
+        let mut world = World::new();
+        world.insert_resource(Settings {
+            move_preview: true,
+            ..default()
+        });
+        world.insert_resource(Windows::default());
+        world
+            .spawn()
+            .insert(Direction::West)
+            .insert(Position::new(0, 3))
+            .insert(Player);
+        let preview = world
+            .spawn()
+            .insert(Sprite::default())
+            .insert(Transform::default())
+            .insert(Visibility::default())
+            .insert(MovePreview)
+            .id();
+        SystemStage::single(move_preview).run(&mut world);
+        assert_eq!(
+            world.get::<Sprite>(preview).unwrap().color,
+            MOVE_PREVIEW_BLOCKED_COLOR
+        );
+        let z = world.get::<Transform>(preview).unwrap().translation.z;
+        assert!(z > 0.0 && z < PLAYER_Z);
+    }
+
+    #[test]
+    fn opposite_of_north_is_south() {
+        assert!(matches!(Direction::North.opposite(), Direction::South));
+    }
+
+    #[test]
+    fn quick_turn_wins_over_a_held_direction() {
+        let mut world = World::new();
+        world.insert_resource(Settings::default());
+        world.insert_resource(Input::<KeyCode>::default());
+        world.insert_resource(Input::<ScanCode>::default());
+        let player = world
+            .spawn()
+            .insert(Direction::East)
+            .insert(Strafe::default())
+            .insert(Player)
+            .id();
+        let mut stage = SystemStage::single(change_player_direction);
+
+        world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+        stage.run(&mut world);
+        assert!(matches!(
+            world.get::<Direction>(player),
+            Some(Direction::North)
+        ));
+
+        world.resource_mut::<Input<KeyCode>>().clear();
+        world.resource_mut::<Input<KeyCode>>().press(KeyCode::Q);
+        stage.run(&mut world);
+        world.resource_mut::<Input<KeyCode>>().clear();
+        stage.run(&mut world);
+        assert!(matches!(
+            world.get::<Direction>(player),
+            Some(Direction::South)
+        ));
+    }
+
+    #[test]
+    fn releasing_a_key_turns_back_to_the_one_still_held() {
+        let mut world = World::new();
+        world.insert_resource(Settings::default());
+        world.insert_resource(Input::<KeyCode>::default());
+        world.insert_resource(Input::<ScanCode>::default());
+        let player = world
+            .spawn()
+            .insert(Direction::South)
+            .insert(Strafe::default())
+            .insert(Player)
+            .id();
+        let mut stage = SystemStage::single(change_player_direction);
+
+        world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+        stage.run(&mut world);
+        world.resource_mut::<Input<KeyCode>>().clear();
+        world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+        stage.run(&mut world);
+        assert!(matches!(
+            world.get::<Direction>(player),
+            Some(Direction::East)
+        ));
+
+        world.resource_mut::<Input<KeyCode>>().clear();
+        world.resource_mut::<Input<KeyCode>>().release(KeyCode::D);
+        stage.run(&mut world);
+        assert!(matches!(
+            world.get::<Direction>(player),
+            Some(Direction::North)
+        ));
+    }
+
+    #[test]
+    fn a_huge_delta_is_clamped_to_the_step_limit() {
+        assert_eq!(steps_due(0.0, 10.0, MIN_STEP_INTERVAL, 3).0, 3);
+        assert_eq!(steps_due(0.0, 10.0, 0.5, 3), (3, 0.0));
+    }
+
+    #[test]
+    fn leftover_time_carries_to_the_next_frame() {
+        assert_eq!(steps_due(0.25, 0.5, 0.5, 3), (1, 0.25));
+        assert_eq!(steps_due(0.25, 0.125, 0.5, 3), (0, 0.375));
+    }
+
+    struct StepCount(u32);
+
+    fn count_step(mut count: ResMut<StepCount>) {
+        count.0 += 1;
+    }
+
+    fn steps_in_four_seconds(speed: f32) -> u32 {
+        let mut world = World::new();
+        world.insert_resource(Settings {
+            game_speed: speed,
+            ..default()
+        });
+        world.insert_resource(StepInterval(0.5));
+        world.insert_resource(StepAccumulator::default());
+        world.insert_resource(StepCount(0));
+        let mut time = Time::default();
+        time.update();
+        world.insert_resource(time);
+        let mut stage = SystemStage::single_threaded().with_system_set(
+            SystemSet::new()
+                .with_run_criteria(step_timer)
+                .with_system(count_step),
+        );
+        for _ in 0..16 {
+            let mut time = world.resource_mut::<Time>();
+            let last_update = time.last_update().unwrap();
+            time.update_with_instant(last_update + std::time::Duration::from_millis(250));
+            stage.run(&mut world);
+        }
+        world.resource::<StepCount>().0
+    }
+
+    #[test]
+    fn half_speed_takes_half_the_steps() {
+        assert_eq!(steps_in_four_seconds(1.0), 8);
+        assert_eq!(steps_in_four_seconds(0.5), 4);
+    }
+
+    #[test]
+    fn invalid_game_speeds_fall_back_to_normal() {
+        assert_eq!(GameSpeed::new(-1.0).0, 1.0);
+        assert_eq!(GameSpeed::new(f32::NAN).0, 1.0);
+    }
+
+    #[test]
+    fn physical_keys_are_read_by_scan_code() {
+        let mut world = World::new();
+        world.insert_resource(Settings {
+            use_physical_keys: true,
+            ..default()
+        });
+        world.insert_resource(Input::<KeyCode>::default());
+        world.insert_resource(Input::<ScanCode>::default());
+        world.insert_resource(Events::<KeyboardInput>::default());
+        let player = world
+            .spawn()
+            .insert(Direction::East)
+            .insert(Strafe::default())
+            .insert(Player)
+            .id();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(scan_code_input)
+            .with_system(change_player_direction.after(scan_code_input));
+
+        // The key in W's place on a QWERTY keyboard is Z on AZERTY.
+        world.send_event(KeyboardInput {
+            scan_code: scan_code_for(KeyCode::W).unwrap().0,
+            key_code: Some(KeyCode::Z),
+            state: ButtonState::Pressed,
+        });
+        stage.run(&mut world);
+        assert!(matches!(
+            world.get::<Direction>(player),
+            Some(Direction::North)
+        ));
+    }
+
+    #[test]
+    fn dash_lands_then_cools_down() {
+        let mut world = World::new();
+        world.insert_resource(Settings {
+            dash_cooldown: 2,
+            ..default()
+        });
+        world.insert_resource(Input::<KeyCode>::default());
+        let player = world
+            .spawn()
+            .insert(Direction::East)
+            .insert(Position::new(0, 0))
+            .insert(Moving(false, true))
+            .insert(Dash {
+                tiles_remaining: 3,
+                cooldown: 0,
+            })
+            .insert(Player)
+            .id();
+        let mut walk = SystemStage::single(entity_walk);
+        let mut dash = SystemStage::single(dash_player);
+
+        walk.run(&mut world);
+        assert_eq!(world.get::<Position>(player), Some(&Position::new(3, 0)));
+        assert_eq!(world.get::<Dash>(player).unwrap().cooldown, 2);
+
+        world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::LShift);
+        dash.run(&mut world);
+        assert_eq!(world.get::<Dash>(player).unwrap().tiles_remaining, 0);
+
+        walk.run(&mut world);
+        walk.run(&mut world);
+        assert_eq!(world.get::<Dash>(player).unwrap().cooldown, 0);
+        dash.run(&mut world);
+        assert_eq!(world.get::<Dash>(player).unwrap().tiles_remaining, 3);
+    }
 }